@@ -0,0 +1,80 @@
+//! Human-readable parse errors with position information.
+
+use nom::error::ErrorKind;
+use std::fmt;
+
+/// A JSON parse failure, carrying the byte offset it occurred at, the
+/// derived line/column, and a caret-annotated snippet of the source line
+/// suitable for printing straight to a terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub snippet: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(input: &str, offset: usize, message: impl Into<String>) -> Self {
+        let (line, column) = line_col(input, offset);
+        let snippet = render_snippet(input, offset, column);
+        ParseError {
+            offset,
+            line,
+            column,
+            message: message.into(),
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} at line {}, column {}", self.message, self.line, self.column)?;
+        write!(f, "{}", self.snippet)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn line_col(input: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for c in input[..offset.min(input.len())].chars() {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+fn render_snippet(input: &str, offset: usize, column: usize) -> String {
+    let offset = offset.min(input.len());
+    let line_start = input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = input[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(input.len());
+    let source_line = &input[line_start..line_end];
+    let caret = format!("{}^", " ".repeat(column.saturating_sub(1)));
+    format!("{source_line}\n{caret}")
+}
+
+/// Maps a nom error kind to a short, user-facing message. `nom` itself only
+/// reports which combinator failed, not the JSON-level intent, so this is
+/// necessarily an approximation rather than a precise grammar diagnostic.
+pub(crate) fn describe_error_kind(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Char => "expected a specific character",
+        ErrorKind::Tag => "expected a keyword or literal",
+        ErrorKind::Digit | ErrorKind::OneOf => "expected a digit",
+        ErrorKind::Eof => "unexpected end of input",
+        ErrorKind::Verify => "invalid escape sequence",
+        ErrorKind::MapRes => "number out of range",
+        _ => "unexpected token while parsing JSON",
+    }
+}
@@ -1,13 +1,19 @@
 use nom::{
     branch::alt,
-    bytes::complete::{escaped, is_not, tag},
-    character::complete::{char, digit1, multispace0},
-    combinator::{map, map_res, peek, recognize},
-    multi::separated_list0,
-    sequence::{delimited, preceded, tuple},
-    IResult, Parser,
+    bytes::complete::{is_not, tag, take},
+    character::complete::{char, digit0, digit1, multispace0, one_of},
+    combinator::{cut, map, map_res, opt, peek, recognize, value, verify},
+    error::{Error, ErrorKind},
+    multi::fold_many0,
+    sequence::{delimited, pair, preceded, tuple},
+    Err, IResult, Offset,
 };
 
+mod error;
+pub mod path;
+
+pub use error::ParseError;
+
 #[derive(Debug, PartialEq)]
 pub enum JsonValue {
     Object(Vec<(String, JsonValue)>),
@@ -18,28 +24,114 @@ pub enum JsonValue {
     Null,
 }
 
-pub fn parse_string(input: &str) -> IResult<&str, String> {
-    let (input, string) = delimited(
-        char('"'),
-        escaped(is_not("\\\""), '\\', char('"')),
-        char('"'),
-    )(input)?;
-    Ok((input, string.to_owned()))
+/// A chunk of a JSON string: either a run of characters with no escapes, or
+/// a single character produced by decoding one escape sequence.
+#[derive(Debug, PartialEq)]
+enum StringFragment<'a> {
+    Literal(&'a str),
+    EscapedChar(char),
 }
 
-pub fn parse_number(input: &str) -> IResult<&str, f64> {
-    let integer_parser = map_res(digit1, |s: &str| s.parse::<f64>());
-    let integer_parser_2 = map_res(digit1, |s: &str| s.parse::<f64>());
+fn parse_literal(input: &str) -> IResult<&str, &str> {
+    verify(is_not("\\\""), |s: &str| !s.is_empty())(input)
+}
+
+fn parse_hex4(input: &str) -> IResult<&str, u16> {
+    map_res(take(4usize), |hex: &str| u16::from_str_radix(hex, 16))(input)
+}
+
+fn fail(input: &str) -> nom::Err<Error<&str>> {
+    Err::Failure(Error::new(input, ErrorKind::Verify))
+}
+
+/// Decodes a `\uXXXX` escape, combining a high/low surrogate pair (as found
+/// in JSON strings containing astral characters) into a single `char` per
+/// the formula `(high - 0xD800) << 10 + (low - 0xDC00) + 0x10000`.
+fn parse_unicode_escape(input: &str) -> IResult<&str, char> {
+    let (input, _) = char('u')(input)?;
+    let (input, high) = parse_hex4(input)?;
+
+    if (0xD800..=0xDBFF).contains(&high) {
+        let (input, _) = tag("\\u")(input)?;
+        let (input, low) = parse_hex4(input)?;
+        if !(0xDC00..=0xDFFF).contains(&low) {
+            return Err(fail(input));
+        }
+        let code_point = 0x10000 + (((high - 0xD800) as u32) << 10) + (low - 0xDC00) as u32;
+        let c = char::from_u32(code_point).ok_or_else(|| fail(input))?;
+        Ok((input, c))
+    } else if (0xDC00..=0xDFFF).contains(&high) {
+        Err(fail(input))
+    } else {
+        let c = char::from_u32(high as u32).ok_or_else(|| fail(input))?;
+        Ok((input, c))
+    }
+}
+
+fn parse_escaped_char(input: &str) -> IResult<&str, char> {
+    preceded(
+        char('\\'),
+        alt((
+            parse_unicode_escape,
+            value('\n', char('n')),
+            value('\t', char('t')),
+            value('\r', char('r')),
+            value('\u{08}', char('b')),
+            value('\u{0C}', char('f')),
+            value('\\', char('\\')),
+            value('/', char('/')),
+            value('"', char('"')),
+        )),
+    )(input)
+}
 
-    let fractional_parser = map_res(digit1, |s: &str| s.parse::<f64>())
-        .map(|fractional| fractional / 10f64.powi(fractional.to_string().len() as i32));
+fn parse_fragment(input: &str) -> IResult<&str, StringFragment<'_>> {
+    alt((
+        map(parse_literal, StringFragment::Literal),
+        map(parse_escaped_char, StringFragment::EscapedChar),
+    ))(input)
+}
 
-    let mut number_parser = alt((
-        recognize(tuple((integer_parser, char('.'), fractional_parser))),
-        recognize(integer_parser_2),
+pub fn parse_string(input: &str) -> IResult<&str, String> {
+    let build_string = fold_many0(parse_fragment, String::new, |mut string, fragment| {
+        match fragment {
+            StringFragment::Literal(s) => string.push_str(s),
+            StringFragment::EscapedChar(c) => string.push(c),
+        }
+        string
+    });
+    delimited(char('"'), build_string, char('"'))(input)
+}
+
+pub fn parse_number(input: &str) -> IResult<&str, f64> {
+    // Mirrors the JSON number production from RFC 8259:
+    //   number = [ "-" ] int [ frac ] [ exp ]
+    //   int    = "0" / (digit1-9 *DIGIT)
+    // Recognizing the whole span and parsing it once with `str::parse` (rather
+    // than reconstructing the value from its digit runs) keeps the result
+    // exact, the same trick `nom`'s own `number::complete::double` uses.
+    let sign = opt(char('-'));
+    let integer_part = alt((
+        recognize(char('0')),
+        recognize(pair(one_of("123456789"), digit0)),
     ));
+    let fraction = opt(recognize(pair(char('.'), digit1)));
+    let exponent = opt(recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1))));
 
-    number_parser(input).map(|(remaining, number)| (remaining, number.parse().unwrap()))
+    map_res(
+        recognize(tuple((sign, integer_part, fraction, exponent))),
+        |s: &str| {
+            // A syntactically valid literal can still overflow `f64` (e.g.
+            // `1e400`); reject that rather than silently producing an
+            // infinity that can't be serialized back to valid JSON.
+            let value: f64 = s.parse().map_err(|_| ())?;
+            if value.is_finite() {
+                Ok(value)
+            } else {
+                Err(())
+            }
+        },
+    )(input)
 }
 
 pub fn parse_boolean(input: &str) -> IResult<&str, bool> {
@@ -51,48 +143,274 @@ pub fn parse_null(input: &str) -> IResult<&str, ()> {
 }
 
 pub fn parse_value(input: &str) -> IResult<&str, JsonValue> {
-    preceded(
-        multispace0,
-        alt((
-            parse_object,
-            parse_array,
-            map(parse_string, JsonValue::String),
-            map(parse_number, JsonValue::Number),
-            map(parse_boolean, JsonValue::Boolean),
-            map(parse_null, |_| JsonValue::Null),
-        )),
-    )(input)
+    let (input, _) = multispace0(input)?;
+
+    // `alt`'s default error policy just keeps the *last* branch's error
+    // (see `nom::error::ParseError::or`), which would throw away a much
+    // more specific error from an earlier branch that got deep into a
+    // nested `{`/`[` before failing. Track the error that consumed the
+    // most input instead, so a deep failure inside `parse_object` or
+    // `parse_array` isn't discarded in favor of `parse_null` immediately
+    // failing at the start.
+    type ValueParser = fn(&str) -> IResult<&str, JsonValue>;
+    let branches: [ValueParser; 6] = [
+        parse_object,
+        parse_array,
+        |i: &str| map(parse_string, JsonValue::String)(i),
+        |i: &str| map(parse_number, JsonValue::Number)(i),
+        |i: &str| map(parse_boolean, JsonValue::Boolean)(i),
+        |i: &str| map(parse_null, |_| JsonValue::Null)(i),
+    ];
+
+    let mut furthest: Option<Err<Error<&str>>> = None;
+    for branch in branches {
+        match branch(input) {
+            Ok(result) => return Ok(result),
+            Err(Err::Incomplete(needed)) => return Err(Err::Incomplete(needed)),
+            // A `Failure` means a branch committed to being the right one
+            // (see the `cut` points in `parse_object`/`parse_array`) and
+            // then hit a real syntax error, so stop trying alternatives.
+            Err(e @ Err::Failure(_)) => return Err(e),
+            Err(e) => furthest = Some(keep_furthest(furthest, e)),
+        }
+    }
+
+    Err(furthest.unwrap_or_else(|| Err::Error(Error::new(input, ErrorKind::Alt))))
 }
 
-pub fn parse_object(input: &str) -> IResult<&str, JsonValue> {
-    let parse_pair = tuple((parse_string, preceded(multispace0, char(':')), parse_value));
-    let parse_object = delimited(
-        preceded(multispace0, char('{')),
-        separated_list0(preceded(multispace0, char(',')), parse_pair),
-        preceded(multispace0, char('}')),
-    );
-    map(parse_object, |pairs| {
-        let mut object = Vec::new();
-        for (key, _, value) in pairs {
-            object.push((key, value));
+/// Keeps whichever of two `Err::Error`s consumed more of the input before
+/// failing, i.e. the one whose remaining `input` is shortest. On a tie,
+/// prefers the candidate only if `error_specificity` ranks its kind higher,
+/// and otherwise keeps the current one: the structural branches (`parse_object`,
+/// `parse_array`) are tried first and fail at the very first character on
+/// any non-`{`/`[` input, so letting them win a tie would bury a more
+/// specific error (e.g. a numeric overflow from `parse_number`) behind a
+/// generic "expected a specific character" message.
+fn keep_furthest<'a>(
+    current: Option<Err<Error<&'a str>>>,
+    candidate: Err<Error<&'a str>>,
+) -> Err<Error<&'a str>> {
+    let candidate_err = match &candidate {
+        Err::Error(e) => e,
+        _ => return candidate,
+    };
+    let current_err = match &current {
+        Some(Err::Error(e)) => Some(e),
+        _ => None,
+    };
+    match current_err {
+        Some(current_err) if current_err.input.len() < candidate_err.input.len() => {
+            current.unwrap()
+        }
+        Some(current_err) if current_err.input.len() == candidate_err.input.len() => {
+            if error_specificity(candidate_err.code) > error_specificity(current_err.code) {
+                candidate
+            } else {
+                current.unwrap()
+            }
+        }
+        _ => candidate,
+    }
+}
+
+/// Ranks error kinds so a tie in `keep_furthest` prefers the more
+/// informative one. `parse_object`/`parse_array` fail at the *original*
+/// input on any non-`{`/`[` character (a structural, "not this branch"
+/// check), so their `ErrorKind::Char` always ties in length with anything
+/// else that also fails at the start of input — e.g. `parse_number`'s
+/// `map_res` overflow check, which `nom` reports at its original input
+/// too. Without this, a number like `1e400` gets blamed on a missing `{`.
+fn error_specificity(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::Char | ErrorKind::Tag => 0,
+        _ => 1,
+    }
+}
+
+/// Parses a `sep`-delimited sequence of elements the way `separated_list0`
+/// does, except the list's end is decided by peeking for `end` rather than
+/// by whether the first element fails to parse. That distinction matters:
+/// a plain `separated_list0` can't tell "the list is empty" apart from
+/// "the first element is malformed" (both look like the first `element`
+/// call returning `Err::Error`), so it silently treats a real error like a
+/// missing value the same as an empty list and reports the failure at
+/// whatever comes after — typically the closing delimiter, which has
+/// nothing to do with the actual problem. Once we know (via the peek)
+/// that the list isn't empty, any element failure is `cut`, including the
+/// first one, so it's reported at its true position instead.
+fn cut_separated_list0<'a, O>(
+    sep: char,
+    end: char,
+    mut element: impl FnMut(&'a str) -> IResult<&'a str, O>,
+    input: &'a str,
+) -> IResult<&'a str, Vec<O>> {
+    let mut items = Vec::new();
+
+    let at_end: IResult<&str, char> = peek(preceded(multispace0, char(end)))(input);
+    if at_end.is_ok() {
+        return Ok((input, items));
+    }
+
+    let mut remaining = match cut(&mut element)(input) {
+        Ok((rest, item)) => {
+            items.push(item);
+            rest
         }
-        JsonValue::Object(object)
-    })(input)
+        Err(e) => return Err(e),
+    };
+
+    loop {
+        remaining = match preceded(multispace0, char(sep))(remaining) {
+            Ok((rest, _)) => rest,
+            Err(Err::Error(_)) => return Ok((remaining, items)),
+            Err(e) => return Err(e),
+        };
+        let (rest, item) = cut(&mut element)(remaining)?;
+        items.push(item);
+        remaining = rest;
+    }
+}
+
+fn parse_pair(input: &str) -> IResult<&str, (String, JsonValue)> {
+    let (input, key) = preceded(multispace0, parse_string)(input)?;
+    let (input, _) = cut(preceded(multispace0, char(':')))(input)?;
+    let (input, value) = cut(parse_value)(input)?;
+    Ok((input, (key, value)))
+}
+
+pub fn parse_object(input: &str) -> IResult<&str, JsonValue> {
+    let (input, _) = preceded(multispace0, char('{'))(input)?;
+    let (input, pairs) = cut_separated_list0(',', '}', parse_pair, input)?;
+    let (input, _) = cut(preceded(multispace0, char('}')))(input)?;
+
+    Ok((input, JsonValue::Object(pairs)))
 }
 
 pub fn parse_array(input: &str) -> IResult<&str, JsonValue> {
-    let parse_array = delimited(
-        preceded(multispace0, char('[')),
-        separated_list0(preceded(multispace0, char(',')), parse_value),
-        preceded(multispace0, char(']')),
-    );
-    map(parse_array, JsonValue::Array)(input)
+    let (input, _) = preceded(multispace0, char('['))(input)?;
+    let (input, items) = cut_separated_list0(',', ']', parse_value, input)?;
+    let (input, _) = cut(preceded(multispace0, char(']')))(input)?;
+
+    Ok((input, JsonValue::Array(items)))
 }
 
 pub fn parse_json(input: &str) -> IResult<&str, JsonValue> {
     preceded(multispace0, parse_value)(input)
 }
 
+/// Parses a complete JSON document, returning a [`ParseError`] with a
+/// line/column position and a caret snippet on failure instead of a bare
+/// `nom::Err`.
+pub fn parse(input: &str) -> Result<JsonValue, ParseError> {
+    match parse_json(input) {
+        Ok((remaining, value)) if remaining.trim().is_empty() => Ok(value),
+        Ok((remaining, _)) => {
+            let offset = input.offset(remaining);
+            Err(ParseError::new(input, offset, "unexpected trailing input"))
+        }
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+            let offset = input.offset(e.input);
+            Err(ParseError::new(
+                input,
+                offset,
+                error::describe_error_kind(e.code),
+            ))
+        }
+        Err(Err::Incomplete(_)) => Err(ParseError::new(input, input.len(), "unexpected end of input")),
+    }
+}
+
+impl JsonValue {
+    /// Renders this value as compact JSON, with no insignificant whitespace.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, None, 0);
+        out
+    }
+
+    /// Renders this value as JSON indented by `indent` spaces per level.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(indent), 0);
+        out
+    }
+
+    fn write(&self, out: &mut String, indent: Option<usize>, depth: usize) {
+        match self {
+            JsonValue::Object(pairs) => write_object(pairs, out, indent, depth),
+            JsonValue::Array(items) => write_array(items, out, indent, depth),
+            JsonValue::String(s) => write_escaped_string(s, out),
+            JsonValue::Number(n) => out.push_str(&n.to_string()),
+            JsonValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+            JsonValue::Null => out.push_str("null"),
+        }
+    }
+}
+
+fn write_object(pairs: &[(String, JsonValue)], out: &mut String, indent: Option<usize>, depth: usize) {
+    out.push('{');
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_newline_indent(out, indent, depth + 1);
+        write_escaped_string(key, out);
+        out.push(':');
+        if indent.is_some() {
+            out.push(' ');
+        }
+        value.write(out, indent, depth + 1);
+    }
+    if !pairs.is_empty() {
+        write_newline_indent(out, indent, depth);
+    }
+    out.push('}');
+}
+
+fn write_array(items: &[JsonValue], out: &mut String, indent: Option<usize>, depth: usize) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_newline_indent(out, indent, depth + 1);
+        item.write(out, indent, depth + 1);
+    }
+    if !items.is_empty() {
+        write_newline_indent(out, indent, depth);
+    }
+    out.push(']');
+}
+
+fn write_newline_indent(out: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(width) = indent {
+        out.push('\n');
+        out.push_str(&" ".repeat(width * depth));
+    }
+}
+
+/// The exact inverse of the escape decoding in `parse_string`: escapes `"`,
+/// `\`, the named control-character escapes, and any other control
+/// character (U+0000–U+001F, U+007F–U+009F) as `\uXXXX`.
+fn write_escaped_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -103,6 +421,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_string_escapes_test() {
+        assert_eq!(
+            super::parse_string(r#""line\nbreak\ttab\\slash\/back""#),
+            Ok(("", "line\nbreak\ttab\\slash/back".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_string_unicode_escape_test() {
+        assert_eq!(
+            super::parse_string(r#""\u00e9""#),
+            Ok(("", "é".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_string_surrogate_pair_test() {
+        assert_eq!(
+            super::parse_string(r#""\ud83d\ude00""#),
+            Ok(("", "😀".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_string_lone_surrogate_is_error() {
+        assert!(super::parse_string(r#""\ud83d""#).is_err());
+    }
+
     #[test]
     fn parse_decimal_number_test() {
         assert_eq!(super::parse_number("123.456"), Ok(("", 123.456)));
@@ -112,6 +459,97 @@ mod tests {
     fn parse_integer_number_test() {
         assert_eq!(super::parse_number("123"), Ok(("", 123.0)));
     }
+
+    #[test]
+    fn parse_negative_number_test() {
+        assert_eq!(super::parse_number("-5"), Ok(("", -5.0)));
+        assert_eq!(super::parse_number("-5.25"), Ok(("", -5.25)));
+    }
+
+    #[test]
+    fn parse_exponent_number_test() {
+        assert_eq!(super::parse_number("1e10"), Ok(("", 1e10)));
+        assert_eq!(super::parse_number("2.5E-3"), Ok(("", 2.5E-3)));
+        assert_eq!(super::parse_number("0.5"), Ok(("", 0.5)));
+    }
+
+    #[test]
+    fn parse_number_rejects_leading_zero() {
+        // `01` is not read as one number: only the `0` is consumed, and a
+        // full document parse then rejects the leftover `1`.
+        let (remaining, number) = super::parse_number("01").unwrap();
+        assert_eq!(number, 0.0);
+        assert_eq!(remaining, "1");
+        assert!(super::parse("01").is_err());
+    }
+
+    #[test]
+    fn parse_number_rejects_overflow() {
+        assert!(super::parse_number("1e400").is_err());
+    }
+
+    #[test]
+    fn parse_reports_number_overflow_not_a_structural_error() {
+        let error = super::parse("1e400").unwrap_err();
+        assert_eq!(error.message, "number out of range");
+
+        let error = super::parse("[1e400]").unwrap_err();
+        assert_eq!(error.message, "number out of range");
+    }
+
+    #[test]
+    fn parse_reports_line_and_column() {
+        let input = "{\n  \"a\": ,\n}";
+        let error = super::parse(input).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 8);
+        assert!(error.snippet.contains('^'));
+    }
+
+    #[test]
+    fn parse_succeeds_on_valid_document() {
+        assert_eq!(
+            super::parse(r#"{"a": 1}"#),
+            Ok(super::JsonValue::Object(vec![(
+                "a".to_owned(),
+                super::JsonValue::Number(1.0)
+            )]))
+        );
+    }
+
+    #[test]
+    fn to_string_compact_test() {
+        let value = super::JsonValue::Object(vec![
+            ("a".to_owned(), super::JsonValue::Number(1.0)),
+            (
+                "b".to_owned(),
+                super::JsonValue::Array(vec![
+                    super::JsonValue::Boolean(true),
+                    super::JsonValue::Null,
+                ]),
+            ),
+        ]);
+        assert_eq!(value.to_string(), r#"{"a":1,"b":[true,null]}"#);
+    }
+
+    #[test]
+    fn to_string_pretty_test() {
+        let value = super::JsonValue::Object(vec![("a".to_owned(), super::JsonValue::Number(1.0))]);
+        assert_eq!(value.to_string_pretty(2), "{\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn to_string_escapes_string_test() {
+        let value = super::JsonValue::String("line\nbreak\t\"quote\"".to_owned());
+        assert_eq!(value.to_string(), r#""line\nbreak\t\"quote\"""#);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let input = r#"{"a":1,"b":[true,null],"c":"hi"}"#;
+        let value = super::parse(input).unwrap();
+        assert_eq!(value.to_string(), input);
+    }
 }
 
 // #[test]
@@ -0,0 +1,334 @@
+//! A small JSONPath query engine over [`JsonValue`].
+//!
+//! Supports the core selector set: `$` root, `.name` / `['name']` child
+//! access, `[n]` array indexing (negative indices count from the end),
+//! `[start:end:step]` slices, `*` wildcard, and `..name` recursive descent.
+
+use crate::JsonValue;
+use std::fmt;
+
+/// An error produced while tokenizing a JSONPath expression; evaluating a
+/// well-formed path never fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    message: String,
+}
+
+impl PathError {
+    fn new(message: impl Into<String>) -> Self {
+        PathError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Child(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, i64),
+    Wildcard,
+    RecursiveDescent(String),
+}
+
+impl JsonValue {
+    /// Evaluates a JSONPath expression against this tree, returning the
+    /// matched nodes. Each step visits the current nodes in turn and takes
+    /// their children in document order, except that a slice with a negative
+    /// step takes them in reverse; duplicate matches are not removed.
+    pub fn query(&self, path: &str) -> Result<Vec<&JsonValue>, PathError> {
+        let selectors = tokenize(path)?;
+        let mut current = vec![self];
+        for selector in &selectors {
+            current = apply_selector(current, selector);
+        }
+        Ok(current)
+    }
+}
+
+fn tokenize(path: &str) -> Result<Vec<Selector>, PathError> {
+    let mut chars = path.chars().peekable();
+    let mut selectors = Vec::new();
+
+    if chars.peek() == Some(&'$') {
+        chars.next();
+    }
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '.' => {
+                chars.next();
+                if chars.peek() == Some(&'.') {
+                    chars.next();
+                    let name = read_identifier(&mut chars)?;
+                    selectors.push(Selector::RecursiveDescent(name));
+                } else if chars.peek() == Some(&'*') {
+                    chars.next();
+                    selectors.push(Selector::Wildcard);
+                } else {
+                    let name = read_identifier(&mut chars)?;
+                    selectors.push(Selector::Child(name));
+                }
+            }
+            '[' => {
+                chars.next();
+                selectors.push(read_bracket(&mut chars)?);
+            }
+            '*' => {
+                chars.next();
+                selectors.push(Selector::Wildcard);
+            }
+            _ => return Err(PathError::new(format!("unexpected character '{c}' in path"))),
+        }
+    }
+
+    Ok(selectors)
+}
+
+fn read_identifier(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, PathError> {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == '.' || c == '[' {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    if name.is_empty() {
+        return Err(PathError::new("expected a name after '.'"));
+    }
+    Ok(name)
+}
+
+fn read_bracket(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Selector, PathError> {
+    let mut content = String::new();
+    loop {
+        match chars.next() {
+            Some(']') => break,
+            Some(c) => content.push(c),
+            None => return Err(PathError::new("unterminated '[' in path")),
+        }
+    }
+
+    if content == "*" {
+        return Ok(Selector::Wildcard);
+    }
+
+    if (content.starts_with('\'') && content.ends_with('\'') && content.len() >= 2)
+        || (content.starts_with('"') && content.ends_with('"') && content.len() >= 2)
+    {
+        return Ok(Selector::Child(content[1..content.len() - 1].to_owned()));
+    }
+
+    if content.contains(':') {
+        let parts: Vec<&str> = content.splitn(3, ':').collect();
+        let start = parse_optional_index(parts.first().copied().unwrap_or(""))?;
+        let end = parse_optional_index(parts.get(1).copied().unwrap_or(""))?;
+        let step = match parts.get(2).copied().unwrap_or("") {
+            "" => 1,
+            s => s
+                .parse::<i64>()
+                .map_err(|_| PathError::new(format!("invalid slice step '{s}'")))?,
+        };
+        return Ok(Selector::Slice(start, end, step));
+    }
+
+    let index = content
+        .parse::<i64>()
+        .map_err(|_| PathError::new(format!("invalid index '{content}'")))?;
+    Ok(Selector::Index(index))
+}
+
+fn parse_optional_index(s: &str) -> Result<Option<i64>, PathError> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    s.parse::<i64>()
+        .map(Some)
+        .map_err(|_| PathError::new(format!("invalid slice bound '{s}'")))
+}
+
+fn resolve_index(len: usize, index: i64) -> Option<usize> {
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved as usize >= len {
+        None
+    } else {
+        Some(resolved as usize)
+    }
+}
+
+fn index_into(node: &JsonValue, index: i64) -> Option<&JsonValue> {
+    match node {
+        JsonValue::Array(items) => resolve_index(items.len(), index).map(|i| &items[i]),
+        _ => None,
+    }
+}
+
+fn apply_selector<'a>(nodes: Vec<&'a JsonValue>, selector: &Selector) -> Vec<&'a JsonValue> {
+    let mut result = Vec::new();
+    for node in nodes {
+        match selector {
+            Selector::Child(name) => {
+                if let JsonValue::Object(pairs) = node {
+                    for (key, value) in pairs {
+                        if key == name {
+                            result.push(value);
+                        }
+                    }
+                }
+            }
+            Selector::Index(index) => {
+                if let Some(item) = index_into(node, *index) {
+                    result.push(item);
+                }
+            }
+            Selector::Slice(start, end, step) => {
+                if let JsonValue::Array(items) = node {
+                    result.extend(slice(items, *start, *end, *step));
+                }
+            }
+            Selector::Wildcard => match node {
+                JsonValue::Object(pairs) => result.extend(pairs.iter().map(|(_, value)| value)),
+                JsonValue::Array(items) => result.extend(items.iter()),
+                _ => {}
+            },
+            Selector::RecursiveDescent(name) => collect_recursive(node, name, &mut result),
+        }
+    }
+    result
+}
+
+fn slice(items: &[JsonValue], start: Option<i64>, end: Option<i64>, step: i64) -> Vec<&JsonValue> {
+    let len = items.len() as i64;
+    if step == 0 || len == 0 {
+        return Vec::new();
+    }
+
+    let clamp = |value: i64| -> i64 { value.max(0).min(len) };
+    let (start, end) = if step > 0 {
+        let start = clamp(start.map(|s| if s < 0 { s + len } else { s }).unwrap_or(0));
+        let end = clamp(end.map(|e| if e < 0 { e + len } else { e }).unwrap_or(len));
+        (start, end)
+    } else {
+        let start = clamp(start.map(|s| if s < 0 { s + len } else { s }).unwrap_or(len - 1) + 1) - 1;
+        let end = clamp(end.map(|e| if e < 0 { e + len } else { e }).unwrap_or(-1) + 1) - 1;
+        (start, end)
+    };
+
+    let mut result = Vec::new();
+    let mut i = start;
+    if step > 0 {
+        while i < end {
+            result.push(&items[i as usize]);
+            i += step;
+        }
+    } else {
+        while i > end {
+            result.push(&items[i as usize]);
+            i += step;
+        }
+    }
+    result
+}
+
+fn collect_recursive<'a>(node: &'a JsonValue, name: &str, result: &mut Vec<&'a JsonValue>) {
+    match node {
+        JsonValue::Object(pairs) => {
+            for (key, value) in pairs {
+                if key == name {
+                    result.push(value);
+                }
+                collect_recursive(value, name, result);
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_recursive(item, name, result);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_json;
+
+    fn parse(input: &str) -> JsonValue {
+        parse_json(input).unwrap().1
+    }
+
+    #[test]
+    fn child_access_test() {
+        let value = parse(r#"{"name": "John", "age": 42}"#);
+        assert_eq!(
+            value.query("$.name").unwrap(),
+            vec![&JsonValue::String("John".to_owned())]
+        );
+    }
+
+    #[test]
+    fn bracket_child_access_test() {
+        let value = parse(r#"{"name": "John"}"#);
+        assert_eq!(
+            value.query("$['name']").unwrap(),
+            vec![&JsonValue::String("John".to_owned())]
+        );
+    }
+
+    #[test]
+    fn array_index_test() {
+        let value = parse("[1, 2, 3]");
+        assert_eq!(value.query("$[1]").unwrap(), vec![&JsonValue::Number(2.0)]);
+        assert_eq!(value.query("$[-1]").unwrap(), vec![&JsonValue::Number(3.0)]);
+    }
+
+    #[test]
+    fn slice_test() {
+        let value = parse("[1, 2, 3, 4, 5]");
+        assert_eq!(
+            value.query("$[1:3]").unwrap(),
+            vec![&JsonValue::Number(2.0), &JsonValue::Number(3.0)]
+        );
+    }
+
+    #[test]
+    fn wildcard_test() {
+        let value = parse(r#"{"a": 1, "b": 2}"#);
+        assert_eq!(
+            value.query("$.*").unwrap(),
+            vec![&JsonValue::Number(1.0), &JsonValue::Number(2.0)]
+        );
+    }
+
+    #[test]
+    fn recursive_descent_test() {
+        let value = parse(r#"{"a": {"name": "inner"}, "name": "outer"}"#);
+        let mut names: Vec<String> = value
+            .query("$..name")
+            .unwrap()
+            .into_iter()
+            .map(|v| match v {
+                JsonValue::String(s) => s.clone(),
+                _ => panic!("expected string"),
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["inner".to_owned(), "outer".to_owned()]);
+    }
+
+    #[test]
+    fn unterminated_bracket_is_error() {
+        let value = parse(r#"{"a": 1}"#);
+        assert!(value.query("$[").is_err());
+    }
+}